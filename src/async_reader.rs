@@ -1,14 +1,15 @@
 //! Read an EDF file asynhronously (with futures)
 
 use crate::file_reader::AsyncFileReader;
-use crate::model::{EDFHeader, EDF_HEADER_BYTE_SIZE};
+use crate::model::{Annotation, EDFHeader, EDF_HEADER_BYTE_SIZE};
+use crate::parser::parse_tals;
 
 use futures::future::{err, ok, Future};
 use std::io::Error;
 
 pub struct AsyncEDFReader<T: AsyncFileReader> {
     pub edf_header: EDFHeader,
-    file_reader: T,
+    pub(crate) file_reader: T,
 }
 
 impl<T: 'static + AsyncFileReader + Send + Sync + Clone> AsyncEDFReader<T> {
@@ -31,7 +32,7 @@ impl<T: 'static + AsyncFileReader + Send + Sync + Clone> AsyncEDFReader<T> {
                     move |channel_headers_raw| {
                         edf_header.build_channel_headers(channel_headers_raw);
                         ok(AsyncEDFReader {
-                            edf_header: edf_header,
+                            edf_header,
                             file_reader: reader_final,
                         })
                     },
@@ -47,7 +48,7 @@ impl<T: 'static + AsyncFileReader + Send + Sync + Clone> AsyncEDFReader<T> {
         duration_ms: u64,
     ) -> Box<dyn Future<Item = Vec<Vec<f32>>, Error = std::io::Error> + Send> {
         if let Err(e) = super::check_bounds(start_time_ms, duration_ms, &self.edf_header) {
-            return Box::new(err::<Vec<Vec<f32>>, Error>(e));
+            return Box::new(err::<Vec<Vec<f32>>, Error>(e.into()));
         }
         // calculate the corresponding blocks to get
 
@@ -72,26 +73,34 @@ impl<T: 'static + AsyncFileReader + Send + Sync + Clone> AsyncEDFReader<T> {
                 }
 
                 let mut index = 0;
+                let format = header.sample_format;
 
                 for _block_idx in 0..number_of_blocks_to_get {
                     for (j, channel) in header.channels.iter().enumerate() {
                         for _sample_idx in 0..channel.number_of_samples_in_data_record {
-                            let digital_sample = match super::get_sample(&data, index) {
+                            let digital_sample = match super::get_sample(&data, index, format) {
                                 Ok(s) => s as f32,
                                 Err(e) => {
                                     eprintln!(
-                                        "Error reading digital sample at byte index {}: {}",
-                                        index * 2,
+                                        "Error reading digital sample at byte index {}: {:?}",
+                                        index * format.byte_width() as usize,
                                         e
                                     );
-                                    return Err(e);
+                                    return Err(e.into());
                                 }
                             };
-                            result[j].push(
-                                (digital_sample - channel.digital_minimum as f32)
-                                    * channel.scale_factor
-                                    + channel.physical_minimum,
-                            );
+                            // The "EDF Annotations" channel carries Time-stamped
+                            // Annotation Lists, not scaled numeric samples; see
+                            // `read_annotations`.
+                            if channel.label == crate::model::ANNOTATIONS_CHANNEL_LABEL {
+                                result[j].push(digital_sample);
+                            } else {
+                                result[j].push(
+                                    (digital_sample - channel.digital_minimum)
+                                        * channel.scale_factor
+                                        + channel.physical_minimum,
+                                );
+                            }
                             index += 1;
                         }
                     }
@@ -102,4 +111,60 @@ impl<T: 'static + AsyncFileReader + Send + Sync + Clone> AsyncEDFReader<T> {
 
         Box::new(processing_future)
     }
+
+    /// Reads and decodes the Time-stamped Annotation Lists (TALs) carried by the
+    /// "EDF Annotations" channel of the same data records `read_data_window` reads
+    /// for this window. Every TAL in those records is returned; they are not
+    /// filtered by onset.
+    ///
+    /// Resolves to an empty `Vec` if the recording has no annotations channel (i.e.
+    /// it is plain EDF rather than EDF+).
+    pub fn read_annotations(
+        &self,
+        start_time_ms: u64,
+        duration_ms: u64,
+    ) -> Box<dyn Future<Item = Vec<Annotation>, Error = std::io::Error> + Send> {
+        let channel_index = match self.edf_header.annotations_channel_index() {
+            Some(i) => i,
+            None => return Box::new(ok(Vec::new())),
+        };
+
+        if let Err(e) = super::check_bounds(start_time_ms, duration_ms, &self.edf_header) {
+            return Box::new(err::<Vec<Annotation>, Error>(e.into()));
+        }
+
+        let first_block_start_time = start_time_ms - start_time_ms % self.edf_header.block_duration;
+        let first_block_index = first_block_start_time / self.edf_header.block_duration;
+        let number_of_blocks_to_get =
+            (duration_ms as f64 / self.edf_header.block_duration as f64).ceil() as u64;
+        let block_size = self.edf_header.get_size_of_data_block();
+        let offset = self.edf_header.byte_size_header + first_block_index * block_size;
+        let length_to_read = number_of_blocks_to_get * block_size;
+
+        let header = self.edf_header.clone();
+
+        let processing_future = self.file_reader.read_async(offset, length_to_read).and_then(
+            move |data: Vec<u8>| -> Result<Vec<Annotation>, Error> {
+                let stride = header.sample_format.byte_width();
+                let channel_byte_offset: u64 = header.channels[..channel_index]
+                    .iter()
+                    .map(|c| c.number_of_samples_in_data_record * stride)
+                    .sum();
+                let channel_byte_len =
+                    header.channels[channel_index].number_of_samples_in_data_record * stride;
+
+                let mut annotations = Vec::new();
+                for block_idx in 0..number_of_blocks_to_get {
+                    let block_start = block_idx * block_size + channel_byte_offset;
+                    let block_end = block_start + channel_byte_len;
+                    annotations
+                        .extend(parse_tals(&data[block_start as usize..block_end as usize]));
+                }
+
+                Ok(annotations)
+            },
+        );
+
+        Box::new(processing_future)
+    }
 }
@@ -0,0 +1,234 @@
+//! Caches the raw bytes of data records in front of an `AsyncEDFReader`, so that
+//! repeated or overlapping `read_data_window`/`read_annotations` calls over the
+//! same blocks don't re-read the file. Cached records are decoded again on every
+//! call; decoded samples are not cached. A block that's already being
+//! fetched for one caller is shared with any other caller that asks for it
+//! before the read completes, instead of issuing a second read.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use futures::future::{err, ok, Future, Shared};
+
+use crate::async_reader::AsyncEDFReader;
+use crate::file_reader::AsyncFileReader;
+use crate::model::{Annotation, EDFHeader};
+use crate::parser::parse_tals;
+
+type InnerBlockFuture = Box<dyn Future<Item = Arc<Vec<u8>>, Error = Arc<std::io::Error>> + Send>;
+type SharedBlockFuture = Shared<InnerBlockFuture>;
+type BlockFuture = Box<dyn Future<Item = Arc<Vec<u8>>, Error = Arc<std::io::Error>> + Send>;
+
+fn rewrap_error(e: Arc<std::io::Error>) -> std::io::Error {
+    std::io::Error::new(e.kind(), e.to_string())
+}
+
+/// Least-recently-used cache of raw (undecoded) data record bytes, keyed by block index.
+struct Lru {
+    capacity: usize,
+    order: VecDeque<u64>,
+    entries: HashMap<u64, Arc<Vec<u8>>>,
+}
+
+impl Lru {
+    fn new(capacity: usize) -> Lru {
+        Lru {
+            capacity,
+            order: VecDeque::new(),
+            entries: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, key: u64) -> Option<Arc<Vec<u8>>> {
+        let value = self.entries.get(&key).cloned();
+        if value.is_some() {
+            self.touch(key);
+        }
+        value
+    }
+
+    fn insert(&mut self, key: u64, value: Arc<Vec<u8>>) {
+        if self.entries.insert(key, value).is_some() {
+            self.touch(key);
+            return;
+        }
+
+        self.order.push_back(key);
+        if self.entries.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+
+    fn touch(&mut self, key: u64) {
+        if let Some(pos) = self.order.iter().position(|&k| k == key) {
+            self.order.remove(pos);
+        }
+        self.order.push_back(key);
+    }
+}
+
+/// Wraps an `AsyncEDFReader`, caching up to a configurable number of already-read
+/// data records so that viewer-style callers requesting overlapping time windows
+/// hit the cache instead of re-reading the file.
+pub struct CachedEDFReader<T: AsyncFileReader> {
+    reader: Arc<AsyncEDFReader<T>>,
+    cache: Arc<Mutex<Lru>>,
+    in_flight: Arc<Mutex<HashMap<u64, SharedBlockFuture>>>,
+}
+
+impl<T: 'static + AsyncFileReader + Send + Sync + Clone> CachedEDFReader<T> {
+    /// Wraps `reader`, caching up to `capacity_in_blocks` data records.
+    pub fn new(reader: AsyncEDFReader<T>, capacity_in_blocks: usize) -> CachedEDFReader<T> {
+        CachedEDFReader {
+            reader: Arc::new(reader),
+            cache: Arc::new(Mutex::new(Lru::new(capacity_in_blocks))),
+            in_flight: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn edf_header(&self) -> &EDFHeader {
+        &self.reader.edf_header
+    }
+
+    /// Returns the raw bytes of data record `block_index`: from cache if present,
+    /// joining an in-flight read for the same block if one is already underway,
+    /// and otherwise issuing the read itself.
+    fn get_block(&self, block_index: u64) -> BlockFuture {
+        if let Some(cached) = self.cache.lock().unwrap().get(block_index) {
+            return Box::new(ok(cached));
+        }
+
+        let mut in_flight = self.in_flight.lock().unwrap();
+        let shared: SharedBlockFuture = match in_flight.get(&block_index) {
+            Some(existing) => existing.clone(),
+            None => {
+                let header = self.reader.edf_header.clone();
+                let offset =
+                    header.byte_size_header + block_index * header.get_size_of_data_block();
+                let length = header.get_size_of_data_block();
+
+                let cache = self.cache.clone();
+                let in_flight_for_cleanup = self.in_flight.clone();
+
+                let inner: InnerBlockFuture =
+                    Box::new(self.reader.file_reader.read_async(offset, length).then(
+                        move |result| {
+                            // Remove the in-flight entry on both success and failure, so a
+                            // transient read error doesn't poison this block index forever.
+                            in_flight_for_cleanup.lock().unwrap().remove(&block_index);
+                            match result {
+                                Ok(raw) => {
+                                    let raw = Arc::new(raw);
+                                    cache.lock().unwrap().insert(block_index, raw.clone());
+                                    Ok(raw)
+                                }
+                                Err(e) => Err(Arc::new(e)),
+                            }
+                        },
+                    ));
+
+                let shared = inner.shared();
+                in_flight.insert(block_index, shared.clone());
+                shared
+            }
+        };
+        drop(in_flight);
+
+        Box::new(shared.map(|item| (*item).clone()).map_err(|e| (*e).clone()))
+    }
+
+    fn blocks_for_window(&self, start_time_ms: u64, duration_ms: u64) -> (u64, u64) {
+        let block_duration = self.reader.edf_header.block_duration;
+        let first_block_index = (start_time_ms - start_time_ms % block_duration) / block_duration;
+        let number_of_blocks = (duration_ms as f64 / block_duration as f64).ceil() as u64;
+        (first_block_index, number_of_blocks)
+    }
+
+    /// Reads a window of EDF data, serving already-cached data records instead of
+    /// re-reading them.
+    pub fn read_data_window(
+        &self,
+        start_time_ms: u64,
+        duration_ms: u64,
+    ) -> Box<dyn Future<Item = Vec<Vec<f32>>, Error = std::io::Error> + Send> {
+        if let Err(e) = super::check_bounds(start_time_ms, duration_ms, &self.reader.edf_header) {
+            return Box::new(err::<Vec<Vec<f32>>, std::io::Error>(e.into()));
+        }
+
+        let (first_block_index, number_of_blocks) =
+            self.blocks_for_window(start_time_ms, duration_ms);
+        let block_futures: Vec<BlockFuture> = (first_block_index
+            ..first_block_index + number_of_blocks)
+            .map(|idx| self.get_block(idx))
+            .collect();
+
+        let header = self.reader.edf_header.clone();
+
+        Box::new(
+            futures::future::join_all(block_futures)
+                .map_err(rewrap_error)
+                .and_then(move |blocks| {
+                    let mut result: Vec<Vec<f32>> =
+                        vec![Vec::new(); header.number_of_signals as usize];
+                    for raw in blocks {
+                        let decoded = super::decode_block(&raw, &header)?;
+                        for (j, samples) in decoded.into_iter().enumerate() {
+                            result[j].extend(samples);
+                        }
+                    }
+                    Ok(result)
+                }),
+        )
+    }
+
+    /// Reads and decodes the Time-stamped Annotation Lists carried by the "EDF
+    /// Annotations" channel over a window, serving cached data records the same
+    /// way `read_data_window` does.
+    pub fn read_annotations(
+        &self,
+        start_time_ms: u64,
+        duration_ms: u64,
+    ) -> Box<dyn Future<Item = Vec<Annotation>, Error = std::io::Error> + Send> {
+        let channel_index = match self.reader.edf_header.annotations_channel_index() {
+            Some(i) => i,
+            None => return Box::new(ok(Vec::new())),
+        };
+
+        if let Err(e) = super::check_bounds(start_time_ms, duration_ms, &self.reader.edf_header) {
+            return Box::new(err::<Vec<Annotation>, std::io::Error>(e.into()));
+        }
+
+        let (first_block_index, number_of_blocks) =
+            self.blocks_for_window(start_time_ms, duration_ms);
+        let block_futures: Vec<BlockFuture> = (first_block_index
+            ..first_block_index + number_of_blocks)
+            .map(|idx| self.get_block(idx))
+            .collect();
+
+        let header = self.reader.edf_header.clone();
+
+        Box::new(
+            futures::future::join_all(block_futures)
+                .map_err(rewrap_error)
+                .map(move |blocks| {
+                    let stride = header.sample_format.byte_width();
+                    let channel_byte_offset: u64 = header.channels[..channel_index]
+                        .iter()
+                        .map(|c| c.number_of_samples_in_data_record * stride)
+                        .sum();
+                    let channel_byte_len =
+                        header.channels[channel_index].number_of_samples_in_data_record * stride;
+
+                    let mut annotations = Vec::new();
+                    for raw in blocks {
+                        let start = channel_byte_offset as usize;
+                        let end = start + channel_byte_len as usize;
+                        annotations.extend(parse_tals(&raw[start..end]));
+                    }
+                    annotations
+                }),
+        )
+    }
+}
@@ -0,0 +1,48 @@
+//! Crate-local error type.
+//!
+//! The decode path (`get_sample`, `check_bounds`, and `sync_reader`)
+//! is shared between the `std` build and the `no_std` + `embedded-io` build, so it
+//! can't speak `std::io::Error` directly. `EdfError` is what it speaks instead;
+//! `EdfResult` picks the right concrete error type for the active feature so the
+//! std-facing API is unchanged when the `std` feature is on.
+
+#[cfg(feature = "std")]
+use std::io;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdfError {
+    UnexpectedEof,
+    InvalidData,
+    OutOfBounds,
+    Io,
+}
+
+#[cfg(feature = "std")]
+pub type EdfResult<T> = Result<T, io::Error>;
+#[cfg(not(feature = "std"))]
+pub type EdfResult<T> = Result<T, EdfError>;
+
+#[cfg(feature = "std")]
+impl From<EdfError> for io::Error {
+    fn from(e: EdfError) -> io::Error {
+        let kind = match e {
+            EdfError::UnexpectedEof => io::ErrorKind::UnexpectedEof,
+            EdfError::InvalidData => io::ErrorKind::InvalidData,
+            EdfError::OutOfBounds => io::ErrorKind::InvalidInput,
+            EdfError::Io => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, format!("{:?}", e))
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for EdfError {
+    fn from(e: io::Error) -> EdfError {
+        match e.kind() {
+            io::ErrorKind::UnexpectedEof => EdfError::UnexpectedEof,
+            io::ErrorKind::InvalidData => EdfError::InvalidData,
+            io::ErrorKind::InvalidInput => EdfError::OutOfBounds,
+            _ => EdfError::Io,
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! Abstractions over the underlying byte source so the parser does not care
+//! whether the EDF file lives on the local disk, in memory, behind some other
+//! binding (e.g. the DOM FileAPI in WebAssembly), or on an SD card read through
+//! `embedded-io` on a `no_std` target.
+
+use crate::error::EdfResult;
+#[cfg(feature = "embedded-io")]
+use crate::error::EdfError;
+
+#[cfg(feature = "std")]
+use std::vec::Vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+pub trait FileReader {
+    /// Reads `length` bytes starting at `offset`.
+    fn read_sync(&self, offset: u64, length: u64) -> EdfResult<Vec<u8>>;
+}
+
+#[cfg(feature = "std")]
+pub trait AsyncFileReader: Clone {
+    /// Reads `length` bytes starting at `offset`.
+    fn read_async(
+        &self,
+        offset: u64,
+        length: u64,
+    ) -> Box<dyn futures::future::Future<Item = Vec<u8>, Error = std::io::Error> + Send>;
+}
+
+/// Adapts any `embedded-io` `Read + Seek` device (e.g. an SD card block driver)
+/// into a [`FileReader`].
+///
+/// `embedded-io`'s `Read`/`Seek` take `&mut self`, while `FileReader::read_sync`
+/// takes `&self` to match the std-backed implementations; the device is wrapped
+/// in a `RefCell` to bridge the two.
+#[cfg(feature = "embedded-io")]
+pub struct EmbeddedIoFileReader<D> {
+    device: core::cell::RefCell<D>,
+}
+
+#[cfg(feature = "embedded-io")]
+impl<D> EmbeddedIoFileReader<D> {
+    pub fn new(device: D) -> Self {
+        EmbeddedIoFileReader {
+            device: core::cell::RefCell::new(device),
+        }
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<D: embedded_io::Read + embedded_io::Seek> FileReader for EmbeddedIoFileReader<D> {
+    fn read_sync(&self, offset: u64, length: u64) -> EdfResult<Vec<u8>> {
+        let mut device = self.device.borrow_mut();
+        device
+            .seek(embedded_io::SeekFrom::Start(offset))
+            .map_err(|_| EdfError::Io)?;
+
+        let mut buf = alloc::vec![0u8; length as usize];
+        device
+            .read_exact(&mut buf)
+            .map_err(|_| EdfError::UnexpectedEof)?;
+        Ok(buf)
+    }
+}
@@ -2,69 +2,145 @@
  * edf-reader parse metadata of EDF file and can read block of data from this EDF file
  * spec of EDF format : https://www.edfplus.info/specs/edf.html
  *
+ * Builds `no_std` (see the `embedded-io` feature) for bare-metal targets that read
+ * EDF off e.g. an SD card; `std` is on by default and keeps the existing API.
  */
+#![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(feature = "std")]
 extern crate chrono;
+#[cfg(feature = "std")]
 extern crate futures;
+#[cfg(feature = "std")]
+extern crate tokio;
 
-#[macro_use]
-extern crate serde_derive;
-
+#[cfg(feature = "std")]
 pub mod async_reader;
+#[cfg(feature = "std")]
+pub mod cached_reader;
+pub mod error;
 pub mod file_reader;
 pub mod model;
 mod parser;
+#[cfg(feature = "std")]
+pub mod stream_reader;
 pub mod sync_reader;
 
-use std::convert::TryInto;
+use core::convert::TryInto;
 
-use model::EDFHeader;
+use error::EdfError;
+use model::{EDFHeader, SampleFormat};
 
-use std::io::{Error, ErrorKind};
+#[cfg(feature = "std")]
+use std::vec::Vec;
 
-fn get_sample(data: &Vec<u8>, index: usize) -> Result<i16, std::io::Error> {
-    let start = 2 * index;
-    let end = start + 2;
+/// Reads the sample at `index` (0-based, within the whole data window) using the
+/// byte width of `format`: 2 bytes for classic EDF, 3 for BioSemi BDF. BDF's 24-bit
+/// samples are sign-extended into an `i32` so both formats share the same scale
+/// formula downstream.
+fn get_sample(data: &[u8], index: usize, format: SampleFormat) -> Result<i32, EdfError> {
+    let stride = format.byte_width() as usize;
+    let start = stride * index;
+    let end = start + stride;
 
     // Ensure the indices are within the bounds of the data vector
     if end > data.len() {
-        return Err(std::io::Error::new(
-             std::io::ErrorKind::UnexpectedEof,
-             format!("Attempted to read sample bytes beyond data vector bounds (index: {}, needed: {}, len: {})", index, end, data.len())
-         ));
+        return Err(EdfError::UnexpectedEof);
     }
 
-    // Get the 2-byte slice corresponding to the sample
-    let sample_bytes_slice = &data[start..end];
+    let sample_bytes = &data[start..end];
+
+    match format {
+        SampleFormat::Edf16 => {
+            let sample_bytes_array: [u8; 2] = sample_bytes
+                .try_into()
+                .map_err(|_| EdfError::InvalidData)?;
+            Ok(i16::from_le_bytes(sample_bytes_array) as i32)
+        }
+        SampleFormat::Bdf24 => {
+            let mut value = sample_bytes[0] as i32
+                | (sample_bytes[1] as i32) << 8
+                | (sample_bytes[2] as i32) << 16;
+            if sample_bytes[2] & 0x80 != 0 {
+                value |= 0xFF00_0000u32 as i32;
+            }
+            Ok(value)
+        }
+    }
+}
 
-    // Try to convert the slice into a fixed-size array [u8; 2]
-    let sample_bytes_array: [u8; 2] = sample_bytes_slice.try_into().map_err(|e| {
-        // This error should theoretically not happen if bounds check passed and length is 2
-        std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            format!("Failed to convert byte slice to [u8; 2]: {}", e),
-        )
-    })?;
+/// `f64::ceil`, usable under `no_std` (where the method isn't available without
+/// a libm binding) as well as under `std`.
+pub(crate) fn ceil_f64(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    {
+        x.ceil()
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        libm::ceil(x)
+    }
+}
 
-    // Construct the i16 using the standard library function for little-endian bytes
-    Ok(i16::from_le_bytes(sample_bytes_array))
+/// `f64::round`, usable under `no_std` as well as under `std`; see [`ceil_f64`].
+pub(crate) fn round_f64(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    {
+        x.round()
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        libm::round(x)
+    }
 }
 
-fn check_bounds(start_time: u64, duration: u64, edf_header: &EDFHeader) -> Result<(), Error> {
+fn check_bounds(start_time: u64, duration: u64, edf_header: &EDFHeader) -> Result<(), EdfError> {
     if start_time + duration > edf_header.block_duration * edf_header.number_of_blocks {
-        return Err(Error::new(
-            ErrorKind::InvalidInput,
-            "Window is out of bounds",
-        ));
+        Err(EdfError::OutOfBounds)
     } else {
         Ok(())
     }
 }
 
-#[cfg(test)]
+/// Decodes one already-sized data record (`data.len() == edf_header.get_size_of_data_block()`)
+/// into one sample vector per channel, applying the digital→physical scale to every
+/// channel except "EDF Annotations". Shared by anything that decodes a single
+/// record at a time (`stream_reader`, `cached_reader`).
+#[cfg(feature = "std")]
+pub(crate) fn decode_block(data: &[u8], header: &EDFHeader) -> Result<Vec<Vec<f32>>, EdfError> {
+    let format = header.sample_format;
+    let mut result: Vec<Vec<f32>> = Vec::with_capacity(header.number_of_signals as usize);
+    let mut index = 0;
+
+    for channel in header.channels.iter() {
+        let mut samples = Vec::with_capacity(channel.number_of_samples_in_data_record as usize);
+        for _ in 0..channel.number_of_samples_in_data_record {
+            let digital_sample = get_sample(data, index, format)? as f32;
+
+            if channel.label == model::ANNOTATIONS_CHANNEL_LABEL {
+                samples.push(digital_sample);
+            } else {
+                samples.push(
+                    (digital_sample - channel.digital_minimum) * channel.scale_factor
+                        + channel.physical_minimum,
+                );
+            }
+            index += 1;
+        }
+        result.push(samples);
+    }
+
+    Ok(result)
+}
+
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::get_sample;
-    use std::io::ErrorKind;
+    use crate::error::EdfError;
+    use crate::model::SampleFormat;
 
     // Tests successful conversion of little-endian byte pairs to i16.
     #[test]
@@ -72,7 +148,7 @@ mod tests {
         // Test case 1: Positive value
         // Bytes [200, 1] in little-endian correspond to 0x01C8 = 456
         let data1 = vec![200, 1];
-        let result1 = get_sample(&data1, 0);
+        let result1 = get_sample(&data1, 0, SampleFormat::Edf16);
         assert!(
             result1.is_ok(),
             "Expected Ok for data1, got Err: {:?}",
@@ -83,7 +159,7 @@ mod tests {
         // Test case 2: Negative value
         // Bytes [44, 238] in little-endian correspond to 0xEE2C = -4564 (signed 16-bit)
         let data2 = vec![44, 238];
-        let result2 = get_sample(&data2, 0);
+        let result2 = get_sample(&data2, 0, SampleFormat::Edf16);
         assert!(
             result2.is_ok(),
             "Expected Ok for data2, got Err: {:?}",
@@ -93,7 +169,7 @@ mod tests {
 
         // Test case 3: Multiple samples in one vector
         let data3 = vec![200, 1, 44, 238];
-        let result3a = get_sample(&data3, 0);
+        let result3a = get_sample(&data3, 0, SampleFormat::Edf16);
         assert!(
             result3a.is_ok(),
             "Expected Ok for data3[0], got Err: {:?}",
@@ -101,7 +177,7 @@ mod tests {
         );
         assert_eq!(456, result3a.unwrap());
 
-        let result3b = get_sample(&data3, 1);
+        let result3b = get_sample(&data3, 1, SampleFormat::Edf16);
         assert!(
             result3b.is_ok(),
             "Expected Ok for data3[1], got Err: {:?}",
@@ -115,7 +191,7 @@ mod tests {
     fn test_get_sample_out_of_bounds() {
         // Test case 1: Index requires bytes beyond vector length
         let data1 = vec![200, 1];
-        let result1 = get_sample(&data1, 1);
+        let result1 = get_sample(&data1, 1, SampleFormat::Edf16);
         assert!(
             result1.is_err(),
             "Expected Err when reading index 1 from data of length 2"
@@ -123,8 +199,8 @@ mod tests {
         // Check the specific error kind
         match result1 {
             Err(e) => assert_eq!(
-                e.kind(),
-                ErrorKind::UnexpectedEof,
+                e,
+                EdfError::UnexpectedEof,
                 "Expected UnexpectedEof error kind"
             ),
             Ok(_) => panic!("Expected error but got Ok"),
@@ -132,15 +208,15 @@ mod tests {
 
         // Test case 2: Index is valid, but requires second byte which is out of bounds
         let data2 = vec![200]; // Length 1. Index 0 needs bytes 0, 1.
-        let result2 = get_sample(&data2, 0);
+        let result2 = get_sample(&data2, 0, SampleFormat::Edf16);
         assert!(
             result2.is_err(),
             "Expected Err when reading index 0 from data of length 1"
         );
         match result2 {
             Err(e) => assert_eq!(
-                e.kind(),
-                ErrorKind::UnexpectedEof,
+                e,
+                EdfError::UnexpectedEof,
                 "Expected UnexpectedEof error kind"
             ),
             Ok(_) => panic!("Expected error but got Ok"),
@@ -151,15 +227,29 @@ mod tests {
     #[test]
     fn test_get_sample_empty_data() {
         let data: Vec<u8> = vec![];
-        let result = get_sample(&data, 0); // Attempt to read sample 0 from empty vec
+        let result = get_sample(&data, 0, SampleFormat::Edf16); // Attempt to read sample 0 from empty vec
         assert!(result.is_err(), "Expected Err when reading from empty data");
         match result {
             Err(e) => assert_eq!(
-                e.kind(),
-                ErrorKind::UnexpectedEof,
+                e,
+                EdfError::UnexpectedEof,
                 "Expected UnexpectedEof error kind"
             ),
             Ok(_) => panic!("Expected error but got Ok"),
         }
     }
+
+    // Tests sign extension of 24-bit BDF samples into i32.
+    #[test]
+    fn test_get_sample_bdf24_sign_extension() {
+        // 0x01C8FF little-endian bytes [255, 200, 1] => positive value 0x01C8FF
+        let data1 = vec![255, 200, 1];
+        let result1 = get_sample(&data1, 0, SampleFormat::Bdf24);
+        assert_eq!(0x01C8FF, result1.unwrap());
+
+        // Bytes [0x00, 0x00, 0x80] => top bit of the 24-bit value set, sign-extends to -8388608
+        let data2 = vec![0x00, 0x00, 0x80];
+        let result2 = get_sample(&data2, 0, SampleFormat::Bdf24);
+        assert_eq!(-8_388_608, result2.unwrap());
+    }
 }
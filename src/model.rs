@@ -0,0 +1,163 @@
+//! Data model describing a parsed EDF header: the general header, the per-signal
+//! (channel) headers, and the values derived from them.
+
+use crate::parser::{parse_number, parse_string};
+
+#[cfg(feature = "std")]
+use std::{string::String, vec::Vec};
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+pub const EDF_HEADER_BYTE_SIZE: usize = 256;
+
+/// Label used by the EDF+ specification for the channel carrying Time-stamped
+/// Annotation Lists instead of numeric samples.
+pub const ANNOTATIONS_CHANNEL_LABEL: &str = "EDF Annotations";
+
+/// On-disk width and encoding of a single sample.
+///
+/// Classic EDF uses 2-byte little-endian signed integers; BioSemi's BDF variant
+/// widens that to 3 bytes to get more dynamic range out of the same recording
+/// hardware.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SampleFormat {
+    Edf16,
+    Bdf24,
+}
+
+impl SampleFormat {
+    /// Detects the format from the first 8 bytes of the general header: a BDF file
+    /// starts with byte `0xFF` followed by the ASCII text `BIOSEMI`, where an EDF
+    /// file has its version number (`0`, space-padded) there instead.
+    fn detect(version_field: &[u8]) -> SampleFormat {
+        if version_field.first() == Some(&0xFF) && &version_field[1..8] == b"BIOSEMI" {
+            SampleFormat::Bdf24
+        } else {
+            SampleFormat::Edf16
+        }
+    }
+
+    pub fn byte_width(self) -> u64 {
+        match self {
+            SampleFormat::Edf16 => 2,
+            SampleFormat::Bdf24 => 3,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EDFChannel {
+    pub label: String,
+    pub transducer_type: String,
+    pub physical_dimension: String,
+    pub physical_minimum: f32,
+    pub physical_maximum: f32,
+    pub digital_minimum: f32,
+    pub digital_maximum: f32,
+    pub prefiltering: String,
+    pub number_of_samples_in_data_record: u64,
+    pub scale_factor: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct EDFHeader {
+    pub version: String,
+    pub patient_id: String,
+    pub recording_id: String,
+    pub start_date: String,
+    pub start_time: String,
+    pub byte_size_header: u64,
+    pub number_of_blocks: u64,
+    pub block_duration: u64,
+    pub number_of_signals: u64,
+    pub sample_format: SampleFormat,
+    pub channels: Vec<EDFChannel>,
+}
+
+/// One Time-stamped Annotation List entry decoded from an "EDF Annotations" channel.
+///
+/// `onset_ms` is milliseconds from the start of the recording; `duration_ms` is a
+/// length in milliseconds.
+/// The record-start marker that begins every data record (used to detect EDF+D
+/// discontinuities) is represented the same way, with an empty `texts`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Annotation {
+    pub onset_ms: i64,
+    pub duration_ms: Option<i64>,
+    pub texts: Vec<String>,
+}
+
+impl EDFHeader {
+    /// Builds the general (first 256 bytes) part of the header.
+    pub fn build_general_header(data: Vec<u8>) -> EDFHeader {
+        EDFHeader {
+            sample_format: SampleFormat::detect(&data[0..8]),
+            version: parse_string(&data[0..8]),
+            patient_id: parse_string(&data[8..88]),
+            recording_id: parse_string(&data[88..168]),
+            start_date: parse_string(&data[168..176]),
+            start_time: parse_string(&data[176..184]),
+            byte_size_header: parse_number(&data[184..192]),
+            number_of_blocks: parse_number(&data[236..244]),
+            // Stored in seconds in the header; kept in milliseconds here.
+            block_duration: crate::round_f64(parse_number::<f64>(&data[244..252]) * 1000.0) as u64,
+            number_of_signals: parse_number(&data[252..256]),
+            channels: Vec::new(),
+        }
+    }
+
+    /// Builds the per-signal header block that immediately follows the general
+    /// header, filling in `self.channels`.
+    pub fn build_channel_headers(&mut self, data: Vec<u8>) {
+        let n = self.number_of_signals as usize;
+        // Byte width of each per-signal field, in on-disk order. Each field is stored
+        // for all `n` signals before the next field begins.
+        const FIELD_SIZES: [usize; 9] = [16, 80, 8, 8, 8, 8, 8, 80, 8];
+        let field = |field_index: usize, field_size: usize, signal_index: usize| -> Vec<u8> {
+            let field_start = FIELD_SIZES[..field_index].iter().sum::<usize>() * n;
+            let base = field_start + signal_index * field_size;
+            data[base..base + field_size].to_vec()
+        };
+
+        let mut channels = Vec::with_capacity(n);
+        for i in 0..n {
+            let physical_minimum: f32 = parse_number(&field(3, 8, i));
+            let physical_maximum: f32 = parse_number(&field(4, 8, i));
+            let digital_minimum: f32 = parse_number(&field(5, 8, i));
+            let digital_maximum: f32 = parse_number(&field(6, 8, i));
+
+            channels.push(EDFChannel {
+                label: parse_string(&field(0, 16, i)),
+                transducer_type: parse_string(&field(1, 80, i)),
+                physical_dimension: parse_string(&field(2, 8, i)),
+                physical_minimum,
+                physical_maximum,
+                digital_minimum,
+                digital_maximum,
+                prefiltering: parse_string(&field(7, 80, i)),
+                number_of_samples_in_data_record: parse_number(&field(8, 8, i)),
+                scale_factor: (physical_maximum - physical_minimum)
+                    / (digital_maximum - digital_minimum),
+            });
+        }
+
+        self.channels = channels;
+    }
+
+    /// Number of bytes a single data record occupies on disk.
+    pub fn get_size_of_data_block(&self) -> u64 {
+        let stride = self.sample_format.byte_width();
+        self.channels
+            .iter()
+            .map(|c| c.number_of_samples_in_data_record * stride)
+            .sum()
+    }
+
+    /// Index of the "EDF Annotations" channel, if this recording is EDF+.
+    pub fn annotations_channel_index(&self) -> Option<usize> {
+        self.channels
+            .iter()
+            .position(|c| c.label == ANNOTATIONS_CHANNEL_LABEL)
+    }
+}
@@ -0,0 +1,91 @@
+//! Low level helpers that turn the fixed-width ASCII fields of an EDF header,
+//! and the raw bytes of an "EDF Annotations" channel, into Rust values.
+
+use core::str::FromStr;
+
+use crate::model::Annotation;
+
+#[cfg(feature = "std")]
+use std::{string::String, string::ToString, vec::Vec};
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, string::ToString, vec::Vec};
+
+/// Trims an ASCII field (EDF header fields are space-padded) and decodes it as UTF-8,
+/// replacing invalid bytes rather than failing since some recorders emit latin-1.
+pub fn parse_string(data: &[u8]) -> String {
+    String::from_utf8_lossy(data).trim().to_string()
+}
+
+/// Parses a trimmed ASCII field into a numeric type, as used for all the integer
+/// and float fields of the general and per-signal EDF headers.
+pub fn parse_number<T: FromStr>(data: &[u8]) -> T
+where
+    T::Err: core::fmt::Debug,
+{
+    parse_string(data)
+        .parse::<T>()
+        .expect("invalid numeric field in EDF header")
+}
+
+/// Decodes the raw bytes of one "EDF Annotations" channel (i.e. the bytes belonging
+/// to that channel within a single data record) into the Time-stamped Annotation
+/// Lists (TALs) it contains.
+///
+/// Each TAL has the form `Onset[0x15]Duration[0x14]Text[0x14]...[0x00]`, trailing
+/// `0x00` bytes pad the rest of the channel. The first TAL of a record carries the
+/// record's own start time and always has an empty text list; it is kept in the
+/// returned `Vec` (with `onset_ms` reflecting whatever the recorder wrote) so the
+/// caller can detect EDF+D discontinuities by comparing it against the nominal
+/// `block_index * block_duration` for that record.
+pub fn parse_tals(data: &[u8]) -> Vec<Annotation> {
+    let mut annotations = Vec::new();
+
+    for tal_bytes in data.split(|&b| b == 0x00) {
+        if tal_bytes.is_empty() {
+            continue;
+        }
+
+        let mut fields = tal_bytes.split(|&b| b == 0x14);
+
+        let timing = match fields.next() {
+            Some(t) if !t.is_empty() => t,
+            _ => continue,
+        };
+
+        let mut timing_parts = timing.split(|&b| b == 0x15);
+        let onset_raw = match timing_parts.next() {
+            Some(o) => o,
+            None => continue,
+        };
+        let onset_seconds: f64 = match String::from_utf8_lossy(onset_raw).parse() {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        let onset_ms = crate::round_f64(onset_seconds * 1000.0) as i64;
+
+        let duration_ms = timing_parts.next().and_then(|d| {
+            if d.is_empty() {
+                None
+            } else {
+                String::from_utf8_lossy(d)
+                    .parse::<f64>()
+                    .ok()
+                    .map(|v| crate::round_f64(v * 1000.0) as i64)
+            }
+        });
+
+        let texts: Vec<String> = fields
+            .filter(|t| !t.is_empty())
+            .map(|t| String::from_utf8_lossy(t).to_string())
+            .collect();
+
+        annotations.push(Annotation {
+            onset_ms,
+            duration_ms,
+            texts,
+        });
+    }
+
+    annotations
+}
@@ -0,0 +1,149 @@
+//! Stream EDF data records out of any `tokio::io::AsyncRead`, for sources that
+//! can't be read at arbitrary offsets (pipes, sockets) and shouldn't be buffered
+//! into memory in full, unlike `AsyncEDFReader`/`EDFReader`.
+
+use std::collections::VecDeque;
+use std::io::{Error, ErrorKind};
+use std::mem;
+
+use futures::{Async, Poll, Stream};
+use tokio::io::AsyncRead;
+
+use crate::model::{EDFHeader, EDF_HEADER_BYTE_SIZE};
+
+enum State {
+    AwaitingGeneralHeader,
+    AwaitingChannelHeaders(EDFHeader),
+    Streaming(EDFHeader),
+    Done,
+}
+
+/// Pulls EDF data records, one at a time, off a byte stream as they arrive.
+///
+/// Internally buffers the bytes needed to decode the header or the next data
+/// record, plus at most one 4 KiB read beyond them, so memory use stays bounded
+/// regardless of recording length.
+pub struct StreamingEDFReader<T: AsyncRead> {
+    source: T,
+    buffer: VecDeque<u8>,
+    state: State,
+}
+
+impl<T: AsyncRead> StreamingEDFReader<T> {
+    pub fn new(source: T) -> StreamingEDFReader<T> {
+        StreamingEDFReader {
+            source,
+            buffer: VecDeque::new(),
+            state: State::AwaitingGeneralHeader,
+        }
+    }
+
+    /// Pulls bytes from the underlying source into `self.buffer` until it holds
+    /// at least `n` bytes, or there isn't enough data available without blocking.
+    ///
+    /// Resolves to `Ready(true)` once `n` bytes are buffered, `Ready(false)` if
+    /// the source hit a clean EOF before any bytes were buffered for this call
+    /// (a record boundary), or errors with `UnexpectedEof` if EOF arrives after
+    /// a partial record has already been buffered.
+    fn fill_buffer(&mut self, n: usize) -> Poll<bool, Error> {
+        let mut chunk = [0u8; 4096];
+        while self.buffer.len() < n {
+            match self.source.poll_read(&mut chunk) {
+                Ok(Async::Ready(0)) => {
+                    if self.buffer.is_empty() {
+                        return Ok(Async::Ready(false));
+                    }
+                    return Err(Error::new(
+                        ErrorKind::UnexpectedEof,
+                        "stream ended before the expected number of bytes arrived",
+                    ));
+                }
+                Ok(Async::Ready(read)) => self.buffer.extend(chunk[..read].iter().copied()),
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(Async::Ready(true))
+    }
+
+    /// Removes and returns exactly `n` bytes from the front of `self.buffer`,
+    /// reading more from the source first if needed. Resolves to `Ready(None)`
+    /// if the source hit a clean EOF before any bytes were buffered for this
+    /// call, instead of erroring.
+    fn read_exact(&mut self, n: usize) -> Poll<Option<Vec<u8>>, Error> {
+        match self.fill_buffer(n)? {
+            Async::Ready(true) => Ok(Async::Ready(Some(self.buffer.drain(..n).collect()))),
+            Async::Ready(false) => Ok(Async::Ready(None)),
+            Async::NotReady => Ok(Async::NotReady),
+        }
+    }
+}
+
+impl<T: AsyncRead> Stream for StreamingEDFReader<T> {
+    type Item = Vec<Vec<f32>>;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        loop {
+            match mem::replace(&mut self.state, State::Done) {
+                State::AwaitingGeneralHeader => match self.read_exact(EDF_HEADER_BYTE_SIZE)? {
+                    Async::Ready(Some(raw)) => {
+                        self.state = State::AwaitingChannelHeaders(EDFHeader::build_general_header(raw));
+                    }
+                    Async::Ready(None) => {
+                        return Err(Error::new(
+                            ErrorKind::UnexpectedEof,
+                            "stream ended before the general header arrived",
+                        ));
+                    }
+                    Async::NotReady => {
+                        self.state = State::AwaitingGeneralHeader;
+                        return Ok(Async::NotReady);
+                    }
+                },
+                State::AwaitingChannelHeaders(header) => {
+                    let channel_header_len =
+                        header.number_of_signals as usize * EDF_HEADER_BYTE_SIZE;
+                    match self.read_exact(channel_header_len)? {
+                        Async::Ready(Some(raw)) => {
+                            let mut header = header;
+                            header.build_channel_headers(raw);
+                            self.state = State::Streaming(header);
+                        }
+                        Async::Ready(None) => {
+                            return Err(Error::new(
+                                ErrorKind::UnexpectedEof,
+                                "stream ended before the channel headers arrived",
+                            ));
+                        }
+                        Async::NotReady => {
+                            self.state = State::AwaitingChannelHeaders(header);
+                            return Ok(Async::NotReady);
+                        }
+                    }
+                }
+                State::Streaming(header) => {
+                    let block_size = header.get_size_of_data_block() as usize;
+                    match self.read_exact(block_size)? {
+                        Async::Ready(Some(raw)) => {
+                            let decoded = super::decode_block(&raw, &header)?;
+                            self.state = State::Streaming(header);
+                            return Ok(Async::Ready(Some(decoded)));
+                        }
+                        Async::Ready(None) => {
+                            // Clean EOF at a record boundary: the recording ended
+                            // normally, not mid-record.
+                            self.state = State::Done;
+                            return Ok(Async::Ready(None));
+                        }
+                        Async::NotReady => {
+                            self.state = State::Streaming(header);
+                            return Ok(Async::NotReady);
+                        }
+                    }
+                }
+                State::Done => return Ok(Async::Ready(None)),
+            }
+        }
+    }
+}
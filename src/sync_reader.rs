@@ -0,0 +1,152 @@
+//! Read an EDF file synchronously
+
+use crate::error::EdfResult;
+use crate::file_reader::FileReader;
+use crate::model::{Annotation, EDFHeader, EDF_HEADER_BYTE_SIZE};
+use crate::parser::parse_tals;
+
+#[cfg(feature = "std")]
+use std::vec::Vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+pub struct EDFReader<T: FileReader> {
+    pub edf_header: EDFHeader,
+    file_reader: T,
+}
+
+impl<T: FileReader> EDFReader<T> {
+    /**
+    Init an EDFReader with a custom FileReader.
+    It can be usefull if the EDF file is not located in the system file. (ie : we cannot use RandomAccessFile).
+    An example of use : read the file with DOM FileAPI in Webassembly
+    */
+    pub fn init_with_file_reader(file_reader: T) -> EdfResult<EDFReader<T>> {
+        let general_header_raw = file_reader.read_sync(0, 256)?;
+        let mut edf_header = EDFHeader::build_general_header(general_header_raw);
+        let channel_header_len = edf_header.number_of_signals * EDF_HEADER_BYTE_SIZE as u64;
+
+        let channel_headers_raw = file_reader.read_sync(256, channel_header_len)?;
+        edf_header.build_channel_headers(channel_headers_raw);
+
+        Ok(EDFReader {
+            edf_header,
+            file_reader,
+        })
+    }
+
+    /// Reads a window of EDF data.
+    pub fn read_data_window(
+        &self,
+        start_time_ms: u64,
+        duration_ms: u64,
+    ) -> EdfResult<Vec<Vec<f32>>> {
+        super::check_bounds(start_time_ms, duration_ms, &self.edf_header)?;
+        // calculate the corresponding blocks to get
+
+        let first_block_start_time = start_time_ms - start_time_ms % self.edf_header.block_duration;
+        let first_block_index = first_block_start_time / self.edf_header.block_duration;
+        let number_of_blocks_to_get =
+            super::ceil_f64(duration_ms as f64 / self.edf_header.block_duration as f64) as u64;
+        let offset = self.edf_header.byte_size_header
+            + first_block_index * self.edf_header.get_size_of_data_block();
+        let length_to_read = number_of_blocks_to_get * self.edf_header.get_size_of_data_block();
+
+        let data = self.file_reader.read_sync(offset, length_to_read)?;
+
+        let mut result: Vec<Vec<f32>> =
+            Vec::with_capacity(self.edf_header.number_of_signals as usize);
+        for _ in 0..self.edf_header.number_of_signals {
+            result.push(Vec::new());
+        }
+
+        let mut index = 0;
+        let format = self.edf_header.sample_format;
+
+        for _block_idx in 0..number_of_blocks_to_get {
+            for (j, channel) in self.edf_header.channels.iter().enumerate() {
+                for _sample_idx in 0..channel.number_of_samples_in_data_record {
+                    let digital_sample = match super::get_sample(&data, index, format) {
+                        Ok(s) => s as f32,
+                        Err(e) => {
+                            #[cfg(feature = "std")]
+                            {
+                                eprintln!(
+                                    "Error reading digital sample at byte index {}: {:?}",
+                                    index * format.byte_width() as usize,
+                                    e
+                                );
+                                return Err(e.into());
+                            }
+                            #[cfg(not(feature = "std"))]
+                            {
+                                return Err(e);
+                            }
+                        }
+                    };
+
+                    // The "EDF Annotations" channel carries Time-stamped Annotation
+                    // Lists, not scaled numeric samples; see `read_annotations`.
+                    if channel.label == crate::model::ANNOTATIONS_CHANNEL_LABEL {
+                        result[j].push(digital_sample);
+                    } else {
+                        result[j].push(
+                            (digital_sample - channel.digital_minimum) * channel.scale_factor
+                                + channel.physical_minimum,
+                        );
+                    }
+                    index += 1;
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Reads and decodes the Time-stamped Annotation Lists (TALs) carried by the
+    /// "EDF Annotations" channel of the same data records `read_data_window` reads
+    /// for this window. Every TAL in those records is returned; they are not
+    /// filtered by onset.
+    ///
+    /// Returns an empty `Vec` if the recording has no annotations channel (i.e. it
+    /// is plain EDF rather than EDF+).
+    pub fn read_annotations(
+        &self,
+        start_time_ms: u64,
+        duration_ms: u64,
+    ) -> EdfResult<Vec<Annotation>> {
+        let channel_index = match self.edf_header.annotations_channel_index() {
+            Some(i) => i,
+            None => return Ok(Vec::new()),
+        };
+
+        super::check_bounds(start_time_ms, duration_ms, &self.edf_header)?;
+
+        let first_block_start_time = start_time_ms - start_time_ms % self.edf_header.block_duration;
+        let first_block_index = first_block_start_time / self.edf_header.block_duration;
+        let number_of_blocks_to_get =
+            super::ceil_f64(duration_ms as f64 / self.edf_header.block_duration as f64) as u64;
+        let block_size = self.edf_header.get_size_of_data_block();
+        let offset = self.edf_header.byte_size_header + first_block_index * block_size;
+        let length_to_read = number_of_blocks_to_get * block_size;
+
+        let data = self.file_reader.read_sync(offset, length_to_read)?;
+
+        let stride = self.edf_header.sample_format.byte_width();
+        let channel_byte_offset: u64 = self.edf_header.channels[..channel_index]
+            .iter()
+            .map(|c| c.number_of_samples_in_data_record * stride)
+            .sum();
+        let channel_byte_len =
+            self.edf_header.channels[channel_index].number_of_samples_in_data_record * stride;
+
+        let mut annotations = Vec::new();
+        for block_idx in 0..number_of_blocks_to_get {
+            let block_start = block_idx * block_size + channel_byte_offset;
+            let block_end = block_start + channel_byte_len;
+            annotations.extend(parse_tals(&data[block_start as usize..block_end as usize]));
+        }
+
+        Ok(annotations)
+    }
+}